@@ -0,0 +1,341 @@
+use std::{fmt::Debug, net::SocketAddr, sync::Arc};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use easytier_rpc::TunnelInfo;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use quinn::{ClientConfig, Endpoint, ServerConfig, TransportConfig, VarInt};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::tunnels::{build_url_from_socket_addr, TunnelConnCounter, TunnelConnector};
+
+use super::{
+    codec::LengthDelimitedCodec,
+    common::{setup_sokcet2, FramedTunnel},
+    Tunnel, TunnelListener,
+};
+
+/// A QUIC bidirectional stream is a reliable byte stream with no datagram
+/// boundaries of its own, so it's framed the same way `unix://` is: a
+/// length-delimited frame, not a raw-datagram `BytesCodec` frame.
+const QUIC_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+const QUIC_ALPN: &[u8] = b"easytier-quic";
+
+/// A QUIC connection with no certificate-based identity: EasyTier peers
+/// already authenticate each other out-of-band (via `PeerId` / the PSK on
+/// other transports), so the TLS layer here is only asked for the transport
+/// security and multiplexing QUIC gives for free, not for a PKI trust chain.
+/// This mirrors the plain (no-psk) UDP tunnel, which is equally unauthenticated
+/// at the transport level.
+#[derive(Debug)]
+struct SkipServerVerification;
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+fn make_server_config() -> Result<ServerConfig, super::TunnelError> {
+    let cert = rcgen::generate_simple_self_signed(vec!["easytier".to_owned()]).map_err(|e| {
+        super::TunnelError::CommonError(format!("quic self-signed cert generation failed: {}", e))
+    })?;
+    let cert_der = rustls::Certificate(cert.serialize_der().map_err(|e| {
+        super::TunnelError::CommonError(format!("quic cert serialization failed: {}", e))
+    })?);
+    let key_der = rustls::PrivateKey(cert.serialize_private_key_der());
+
+    let mut server_config = ServerConfig::with_single_cert(vec![cert_der], key_der)
+        .map_err(|e| super::TunnelError::CommonError(format!("quic server config: {}", e)))?;
+    Arc::get_mut(&mut server_config.transport)
+        .unwrap()
+        .max_concurrent_bidi_streams(VarInt::from_u32(1));
+    Ok(server_config)
+}
+
+fn make_client_config() -> ClientConfig {
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![QUIC_ALPN.to_vec()];
+
+    let mut transport = TransportConfig::default();
+    transport.max_concurrent_bidi_streams(VarInt::from_u32(1));
+
+    let mut config = ClientConfig::new(Arc::new(crypto));
+    config.transport_config(Arc::new(transport));
+    config
+}
+
+/// Wraps one side of a QUIC bidirectional stream as the tunnel's datagram
+/// pipe: frame with `LengthDelimitedCodec` (see the comment on
+/// `QUIC_MAX_FRAME_LEN` above) and hand stream+sink straight to
+/// `FramedTunnel`.
+fn tunnel_from_bi_stream(
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+) -> Box<dyn Tunnel> {
+    let stream = FramedRead::new(recv, LengthDelimitedCodec::new(QUIC_MAX_FRAME_LEN)).map(|v| {
+        v.map(|b| b.freeze())
+            .map_err(|e| super::TunnelError::CommonError(format!("quic stream error: {}", e)))
+    });
+    let sink =
+        FramedWrite::new(send, LengthDelimitedCodec::new(QUIC_MAX_FRAME_LEN)).sink_map_err(|e| {
+            super::TunnelError::CommonError(format!("quic sink error: {}", e))
+        });
+
+    FramedTunnel::new_tunnel_with_info(
+        Box::pin(stream),
+        Box::pin(sink),
+        TunnelInfo {
+            tunnel_type: "quic".to_owned(),
+            local_addr: build_url_from_socket_addr(&local_addr.to_string(), "quic").into(),
+            remote_addr: build_url_from_socket_addr(&remote_addr.to_string(), "quic").into(),
+        },
+    )
+}
+
+pub struct QuicTunnelListener {
+    addr: url::Url,
+    endpoint: Option<Endpoint>,
+    conns: Arc<DashMap<SocketAddr, ()>>,
+}
+
+impl QuicTunnelListener {
+    pub fn new(addr: url::Url) -> Self {
+        Self {
+            addr,
+            endpoint: None,
+            conns: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl TunnelListener for QuicTunnelListener {
+    async fn listen(&mut self) -> Result<(), super::TunnelError> {
+        let addr =
+            super::check_scheme_and_get_socket_addr::<SocketAddr>(&self.addr, self.addr.scheme())?;
+
+        let socket2_socket = socket2::Socket::new(
+            socket2::Domain::for_address(addr),
+            socket2::Type::DGRAM,
+            Some(socket2::Protocol::UDP),
+        )?;
+        setup_sokcet2(&socket2_socket, &addr)?;
+
+        let server_config = make_server_config()?;
+        let endpoint = Endpoint::new(
+            Default::default(),
+            Some(server_config),
+            socket2_socket.into(),
+            Arc::new(quinn::TokioRuntime),
+        )
+        .map_err(|e| super::TunnelError::CommonError(format!("quic endpoint bind: {}", e)))?;
+
+        self.addr = build_url_from_socket_addr(&endpoint.local_addr()?.to_string(), "quic");
+        self.endpoint = Some(endpoint);
+        Ok(())
+    }
+
+    async fn accept(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let endpoint = self
+            .endpoint
+            .as_ref()
+            .ok_or_else(|| super::TunnelError::CommonError("quic endpoint not listening".to_owned()))?;
+
+        loop {
+            let incoming = endpoint.accept().await.ok_or_else(|| {
+                super::TunnelError::CommonError("quic endpoint closed".to_owned())
+            })?;
+            let conn = match incoming.await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!(?e, "quic incoming connection failed");
+                    continue;
+                }
+            };
+
+            let (send, recv) = match conn.accept_bi().await {
+                Ok(bi) => bi,
+                Err(e) => {
+                    tracing::warn!(?e, "quic accept_bi failed");
+                    continue;
+                }
+            };
+
+            let remote_addr = conn.remote_address();
+            let local_addr = endpoint.local_addr()?;
+            self.conns.insert(remote_addr, ());
+
+            let conns = self.conns.clone();
+            tokio::spawn(async move {
+                conn.closed().await;
+                conns.remove(&remote_addr);
+            });
+
+            return Ok(tunnel_from_bi_stream(send, recv, local_addr, remote_addr));
+        }
+    }
+
+    fn local_url(&self) -> url::Url {
+        self.addr.clone()
+    }
+
+    fn get_conn_counter(&self) -> Arc<Box<dyn TunnelConnCounter>> {
+        #[derive(Debug)]
+        struct QuicTunnelConnCounter {
+            conns: Arc<DashMap<SocketAddr, ()>>,
+        }
+
+        impl TunnelConnCounter for QuicTunnelConnCounter {
+            fn get(&self) -> u32 {
+                self.conns.len() as u32
+            }
+        }
+
+        Arc::new(Box::new(QuicTunnelConnCounter {
+            conns: self.conns.clone(),
+        }))
+    }
+}
+
+pub struct QuicTunnelConnector {
+    addr: url::Url,
+    bind_addrs: Vec<SocketAddr>,
+}
+
+impl QuicTunnelConnector {
+    pub fn new(addr: url::Url) -> Self {
+        Self {
+            addr,
+            bind_addrs: vec![],
+        }
+    }
+
+    async fn try_connect_with_socket(
+        &self,
+        socket: std::net::UdpSocket,
+    ) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let addr =
+            super::check_scheme_and_get_socket_addr::<SocketAddr>(&self.addr, self.addr.scheme())?;
+
+        let mut endpoint = Endpoint::new(
+            Default::default(),
+            None,
+            socket,
+            Arc::new(quinn::TokioRuntime),
+        )
+        .map_err(|e| super::TunnelError::ConnectError(format!("quic endpoint bind: {}", e)))?;
+        endpoint.set_default_client_config(make_client_config());
+
+        let local_addr = endpoint.local_addr()?;
+        let conn = endpoint
+            .connect(addr, "easytier")
+            .map_err(|e| super::TunnelError::ConnectError(format!("quic connect: {}", e)))?
+            .await
+            .map_err(|e| super::TunnelError::ConnectError(format!("quic handshake: {}", e)))?;
+
+        let (send, recv) = conn
+            .open_bi()
+            .await
+            .map_err(|e| super::TunnelError::ConnectError(format!("quic open_bi: {}", e)))?;
+
+        Ok(tunnel_from_bi_stream(send, recv, local_addr, addr))
+    }
+
+    async fn connect_with_default_bind(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let socket = std::net::UdpSocket::bind("0.0.0.0:0")?;
+        self.try_connect_with_socket(socket).await
+    }
+
+    async fn connect_with_custom_bind(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let mut futures = FuturesUnordered::new();
+
+        for bind_addr in self.bind_addrs.iter() {
+            let socket = std::net::UdpSocket::bind(*bind_addr)?;
+
+            // linux does not use interface of bind_addr to send packet, so we need to bind device
+            // mac can handle this with bind correctly
+            #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+            if let Some(dev_name) = super::common::get_interface_name_by_ip(&bind_addr.ip()) {
+                tracing::trace!(dev_name = ?dev_name, "bind device");
+                let socket2_socket = socket2::Socket::from(socket);
+                socket2_socket.bind_device(Some(dev_name.as_bytes()))?;
+                futures.push(self.try_connect_with_socket(socket2_socket.into()));
+                continue;
+            }
+
+            futures.push(self.try_connect_with_socket(socket));
+        }
+
+        let Some(ret) = futures.next().await else {
+            return Err(super::TunnelError::CommonError(
+                "join connect futures failed".to_owned(),
+            ));
+        };
+
+        ret
+    }
+}
+
+#[async_trait]
+impl TunnelConnector for QuicTunnelConnector {
+    async fn connect(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        if self.bind_addrs.is_empty() {
+            self.connect_with_default_bind().await
+        } else {
+            self.connect_with_custom_bind().await
+        }
+    }
+
+    fn remote_url(&self) -> url::Url {
+        self.addr.clone()
+    }
+
+    fn set_bind_addrs(&mut self, addrs: Vec<SocketAddr>) {
+        self.bind_addrs = addrs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tunnels::common::tests::{_tunnel_bench, _tunnel_pingpong};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn quic_pingpong() {
+        let listener = QuicTunnelListener::new("quic://0.0.0.0:5560".parse().unwrap());
+        let connector = QuicTunnelConnector::new("quic://127.0.0.1:5560".parse().unwrap());
+        _tunnel_pingpong(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn quic_bench() {
+        let listener = QuicTunnelListener::new("quic://0.0.0.0:5561".parse().unwrap());
+        let connector = QuicTunnelConnector::new("quic://127.0.0.1:5561".parse().unwrap());
+        _tunnel_bench(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn quic_bench_with_bind() {
+        let listener = QuicTunnelListener::new("quic://127.0.0.1:5562".parse().unwrap());
+        let mut connector = QuicTunnelConnector::new("quic://127.0.0.1:5562".parse().unwrap());
+        connector.set_bind_addrs(vec!["127.0.0.1:0".parse().unwrap()]);
+        _tunnel_pingpong(listener, connector).await
+    }
+}
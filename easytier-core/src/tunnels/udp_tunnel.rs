@@ -1,14 +1,30 @@
-use std::{fmt::Debug, pin::Pin, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use async_trait::async_trait;
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    ChaCha20Poly1305, KeyInit, Nonce,
+};
 use dashmap::DashMap;
 use easytier_rpc::TunnelInfo;
 use futures::{stream::FuturesUnordered, SinkExt, StreamExt};
+use hkdf::Hkdf;
 use rkyv::{Archive, Deserialize, Serialize};
+use sha2::Sha256;
 use std::net::SocketAddr;
-use tokio::{net::UdpSocket, sync::Mutex, task::JoinSet};
+use std::time::Duration;
+use tokio::{net::UdpSocket, sync::Mutex, task::JoinSet, time::Instant};
 use tokio_util::{
     bytes::{Buf, Bytes, BytesMut},
+    sync::CancellationToken,
     udp::UdpFramed,
 };
 use tracing::Instrument;
@@ -19,7 +35,7 @@ use crate::{
 };
 
 use super::{
-    codec::BytesCodec,
+    codec::{BytesCodec, Codec},
     common::{setup_sokcet2, FramedTunnel, TunnelWithCustomInfo},
     ring_tunnel::create_ring_tunnel_pair,
     DatagramSink, DatagramStream, Tunnel, TunnelListener,
@@ -27,15 +43,59 @@ use super::{
 
 pub const UDP_DATA_MTU: usize = 2500;
 
+/// URL scheme used to opt a listener/connector into the reliable, ordered
+/// delivery mode layered on top of raw `UdpPacketPayload::Data` datagrams.
+pub const UDP_RELIABLE_SCHEME: &str = "udp+rel";
+
+/// Overhead of the rkyv-encoded `UdpPacket` envelope around a `RelData`
+/// fragment. Fragments are sized so the whole encoded packet stays under
+/// `UDP_DATA_MTU`.
+const UDP_REL_HEADER_OVERHEAD: usize = 64;
+pub const UDP_REL_DATA_MTU: usize = UDP_DATA_MTU - UDP_REL_HEADER_OVERHEAD;
+
+const REL_RTO_INIT: Duration = Duration::from_millis(200);
+const REL_RTO_MAX: Duration = Duration::from_secs(5);
+const REL_MAX_RETRIES: u32 = 12;
+// Acks are coalesced onto this cadence rather than sent per-fragment, so a
+// batch of fragments arriving together produces a single ack.
+const REL_MAINTENANCE_INTERVAL: Duration = Duration::from_millis(50);
+const REL_MAX_REASSEMBLY_FRAGMENTS: usize = 4096;
+
+const HOLE_PUNCH_BURST_INTERVAL: Duration = Duration::from_millis(100);
+const HOLE_PUNCH_BURST_COUNT: u32 = 30;
+const HOLE_PUNCH_GRACE_PERIOD: Duration = Duration::from_millis(300);
+
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(compare(PartialEq), check_bytes)]
 // Derives can be passed through to the generated type:
 #[archive_attr(derive(Debug))]
 pub enum UdpPacketPayload {
-    Syn,
-    Sack,
+    // carries a random per-side salt, mixed into the PSK-derived directional
+    // keys so nonces never repeat across connections.
+    Syn(u32),
+    Sack(u32),
     HolePunch(Vec<u8>),
     Data(Vec<u8>),
+    // AEAD-encrypted `Data`, used when the tunnel is configured with a PSK.
+    EncryptedData {
+        nonce: [u8; 12],
+        ciphertext: Vec<u8>,
+    },
+    // Reliable mode: a fragment of an ordered message, acked individually.
+    RelData {
+        seq: u64,
+        frag_idx: u16,
+        frag_cnt: u16,
+        bytes: Vec<u8>,
+    },
+    // Reliable mode: `seq` acks everything up to and including it
+    // (cumulative; `u64::MAX` when nothing has been delivered yet),
+    // `recv_window` lists additionally buffered, out-of-order fragment seqs
+    // (selective).
+    RelAck {
+        seq: u64,
+        recv_window: Vec<u64>,
+    },
 }
 
 #[derive(Archive, Deserialize, Serialize, Debug)]
@@ -61,22 +121,58 @@ impl UdpPacket {
         }
     }
 
-    pub fn new_syn_packet(conn_id: u32) -> Self {
+    pub fn new_syn_packet(conn_id: u32, salt: u32) -> Self {
+        Self {
+            conn_id,
+            payload: UdpPacketPayload::Syn(salt),
+        }
+    }
+
+    pub fn new_sack_packet(conn_id: u32, salt: u32) -> Self {
+        Self {
+            conn_id,
+            payload: UdpPacketPayload::Sack(salt),
+        }
+    }
+
+    pub fn new_encrypted_data_packet(conn_id: u32, nonce: [u8; 12], ciphertext: Vec<u8>) -> Self {
         Self {
             conn_id,
-            payload: UdpPacketPayload::Syn,
+            payload: UdpPacketPayload::EncryptedData { nonce, ciphertext },
         }
     }
 
-    pub fn new_sack_packet(conn_id: u32) -> Self {
+    pub fn new_rel_data_packet(
+        conn_id: u32,
+        seq: u64,
+        frag_idx: u16,
+        frag_cnt: u16,
+        bytes: Vec<u8>,
+    ) -> Self {
         Self {
             conn_id,
-            payload: UdpPacketPayload::Sack,
+            payload: UdpPacketPayload::RelData {
+                seq,
+                frag_idx,
+                frag_cnt,
+                bytes,
+            },
+        }
+    }
+
+    pub fn new_rel_ack_packet(conn_id: u32, seq: u64, recv_window: Vec<u64>) -> Self {
+        Self {
+            conn_id,
+            payload: UdpPacketPayload::RelAck { seq, recv_window },
         }
     }
 }
 
-fn try_get_data_payload(mut buf: BytesMut, conn_id: u32) -> Option<BytesMut> {
+fn try_get_data_payload(
+    mut buf: BytesMut,
+    conn_id: u32,
+    cipher: Option<&CipherState>,
+) -> Option<BytesMut> {
     let Ok(udp_packet) = rkyv_util::decode_from_bytes_checked::<UdpPacket>(&buf) else {
         tracing::warn!(?buf, "udp decode error");
         return None;
@@ -87,57 +183,792 @@ fn try_get_data_payload(mut buf: BytesMut, conn_id: u32) -> Option<BytesMut> {
         return None;
     }
 
-    let ArchivedUdpPacketPayload::Data(payload) = &udp_packet.payload else {
-        tracing::warn!(?udp_packet, "udp payload not data");
+    match &udp_packet.payload {
+        ArchivedUdpPacketPayload::Data(payload) => {
+            if cipher.is_some() {
+                tracing::warn!("received unencrypted udp payload but a psk is configured, dropping");
+                return None;
+            }
+            let ptr_range = payload.as_ptr_range();
+            let offset = ptr_range.start as usize - buf.as_ptr() as usize;
+            let len = ptr_range.end as usize - ptr_range.start as usize;
+            buf.advance(offset);
+            buf.truncate(len);
+            tracing::trace!(?offset, ?len, ?buf, "udp payload data");
+            Some(buf)
+        }
+        ArchivedUdpPacketPayload::EncryptedData { nonce, ciphertext } => {
+            let Some(cipher) = cipher else {
+                tracing::warn!("received encrypted udp payload but no psk is configured");
+                return None;
+            };
+            let Some(plaintext) = cipher.decrypt_fresh(conn_id, nonce, ciphertext) else {
+                tracing::warn!("udp payload failed AEAD authentication or replay check, dropping");
+                return None;
+            };
+            Some(BytesMut::from(&plaintext[..]))
+        }
+        _ => {
+            tracing::warn!(?udp_packet, "udp payload not data");
+            None
+        }
+    }
+}
+
+fn derive_directional_keys(
+    psk: &[u8],
+    client_salt: u32,
+    server_salt: u32,
+) -> (chacha20poly1305::Key, chacha20poly1305::Key) {
+    let mut hkdf_salt = [0u8; 8];
+    hkdf_salt[0..4].copy_from_slice(&client_salt.to_be_bytes());
+    hkdf_salt[4..8].copy_from_slice(&server_salt.to_be_bytes());
+    let hk = Hkdf::<Sha256>::new(Some(&hkdf_salt), psk);
+
+    let mut c2s = [0u8; 32];
+    hk.expand(b"c2s", &mut c2s)
+        .expect("32 bytes is a valid chacha20poly1305 key length");
+    let mut s2c = [0u8; 32];
+    hk.expand(b"s2c", &mut s2c)
+        .expect("32 bytes is a valid chacha20poly1305 key length");
+
+    (c2s.into(), s2c.into())
+}
+
+/// Per-connection AEAD state for the optional PSK-encrypted UDP mode.
+/// Directional sub-keys (derived via HKDF over the PSK and both sides'
+/// handshake salts) keep the two directions' nonce spaces independent, so a
+/// shared 32-bit connection salt plus a 64-bit send counter never repeats.
+struct CipherState {
+    encrypt_cipher: ChaCha20Poly1305,
+    decrypt_cipher: ChaCha20Poly1305,
+    salt: u32,
+    send_counter: AtomicU64,
+    // Highest receive-direction nonce counter accepted so far, or u64::MAX
+    // as the sentinel for "nothing accepted yet" (a real counter can never
+    // reach u64::MAX: next_nonce refuses to hand one out).
+    recv_counter: AtomicU64,
+}
+
+impl CipherState {
+    fn new_client(psk: &[u8], client_salt: u32, server_salt: u32) -> Self {
+        let (c2s, s2c) = derive_directional_keys(psk, client_salt, server_salt);
+        Self {
+            encrypt_cipher: ChaCha20Poly1305::new(&c2s),
+            decrypt_cipher: ChaCha20Poly1305::new(&s2c),
+            salt: client_salt,
+            send_counter: AtomicU64::new(0),
+            recv_counter: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    fn new_server(psk: &[u8], client_salt: u32, server_salt: u32) -> Self {
+        let (c2s, s2c) = derive_directional_keys(psk, client_salt, server_salt);
+        Self {
+            encrypt_cipher: ChaCha20Poly1305::new(&s2c),
+            decrypt_cipher: ChaCha20Poly1305::new(&c2s),
+            salt: server_salt,
+            send_counter: AtomicU64::new(0),
+            recv_counter: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    fn next_nonce(&self) -> Option<[u8; 12]> {
+        let Ok(counter) = self
+            .send_counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| c.checked_add(1))
+        else {
+            // wrapped: reusing a nonce under the same key would break AEAD
+            // confidentiality, so force the connection to be torn down
+            // instead of silently rolling over.
+            return None;
+        };
+        let mut nonce = [0u8; 12];
+        nonce[0..4].copy_from_slice(&self.salt.to_be_bytes());
+        nonce[4..12].copy_from_slice(&counter.to_be_bytes());
+        Some(nonce)
+    }
+
+    fn encrypt(&self, conn_id: u32, plaintext: &[u8]) -> Option<([u8; 12], Vec<u8>)> {
+        let nonce = self.next_nonce()?;
+        let aad = conn_id.to_be_bytes();
+        let ciphertext = self
+            .encrypt_cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &aad,
+                },
+            )
+            .ok()?;
+        Some((nonce, ciphertext))
+    }
+
+    fn decrypt(&self, conn_id: u32, nonce: &[u8; 12], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        let aad = conn_id.to_be_bytes();
+        self.decrypt_cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .ok()
+    }
+
+    /// Like `decrypt`, but also rejects replayed/duplicated packets: nonces
+    /// are assigned from a strictly increasing per-direction counter (see
+    /// `next_nonce`), so anything not strictly newer than the highest
+    /// counter accepted so far is a replay (or a legitimate reorder we'd
+    /// rather drop than accept twice) and is rejected.
+    ///
+    /// Used by the plain, unordered `udp://` path, which has no higher-level
+    /// sequencing to dedupe on. The reliable `udp+rel://` path dedupes
+    /// retransmits by fragment `seq` instead -- it resends the exact same
+    /// nonce/ciphertext on retransmit, which this check would reject -- so
+    /// it calls `decrypt` directly rather than this method.
+    fn decrypt_fresh(&self, conn_id: u32, nonce: &[u8; 12], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        // Cheap pre-check before paying for the AEAD decrypt: a stale counter
+        // can only be a replay, so there's no need to authenticate it first.
+        // The window itself only advances below, after decryption succeeds,
+        // so an attacker can't poison it with unauthenticated nonces.
+        let counter = u64::from_be_bytes(nonce[4..12].try_into().unwrap());
+        let highest = self.recv_counter.load(Ordering::SeqCst);
+        if highest != u64::MAX && counter <= highest {
+            return None;
+        }
+
+        let plaintext = self.decrypt(conn_id, nonce, ciphertext)?;
+        let mut highest = self.recv_counter.load(Ordering::SeqCst);
+        loop {
+            if highest != u64::MAX && counter <= highest {
+                return None;
+            }
+            match self.recv_counter.compare_exchange_weak(
+                highest,
+                counter,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return Some(plaintext),
+                Err(actual) => highest = actual,
+            }
+        }
+    }
+}
+
+enum RelRecv {
+    Data {
+        seq: u64,
+        frag_idx: u16,
+        frag_cnt: u16,
+        bytes: Vec<u8>,
+    },
+    Ack {
+        seq: u64,
+        recv_window: Vec<u64>,
+    },
+}
+
+fn try_get_rel_payload(buf: &BytesMut, conn_id: u32) -> Option<RelRecv> {
+    let Ok(udp_packet) = rkyv_util::decode_from_bytes_checked::<UdpPacket>(buf) else {
+        tracing::warn!(?buf, "udp decode error (reliable)");
         return None;
     };
 
-    let ptr_range = payload.as_ptr_range();
-    let offset = ptr_range.start as usize - buf.as_ptr() as usize;
-    let len = ptr_range.end as usize - ptr_range.start as usize;
-    buf.advance(offset);
-    buf.truncate(len);
-    tracing::trace!(?offset, ?len, ?buf, "udp payload data");
+    if udp_packet.conn_id != conn_id {
+        tracing::warn!(?udp_packet, ?conn_id, "udp conn id not match (reliable)");
+        return None;
+    }
+
+    match &udp_packet.payload {
+        ArchivedUdpPacketPayload::RelData {
+            seq,
+            frag_idx,
+            frag_cnt,
+            bytes,
+        } => Some(RelRecv::Data {
+            seq: *seq,
+            frag_idx: *frag_idx,
+            frag_cnt: *frag_cnt,
+            bytes: bytes.to_vec(),
+        }),
+        ArchivedUdpPacketPayload::RelAck { seq, recv_window } => Some(RelRecv::Ack {
+            seq: *seq,
+            recv_window: recv_window.iter().copied().collect(),
+        }),
+        _ => {
+            tracing::warn!(?udp_packet, "udp payload not reliable");
+            None
+        }
+    }
+}
 
-    Some(buf)
+struct RelInFlight {
+    packet: Bytes,
+    sent_at: Instant,
+    rto: Duration,
+    retries: u32,
 }
 
-fn get_tunnel_from_socket(
+#[derive(Default)]
+struct RelSendState {
+    next_seq: u64,
+    unacked: BTreeMap<u64, RelInFlight>,
+}
+
+#[derive(Default)]
+struct RelRecvState {
+    // fragment seq -> (frag_idx, frag_cnt, bytes)
+    reassembly: BTreeMap<u64, (u16, u16, Vec<u8>)>,
+    // base seq of the next message we're waiting to deliver, in order
+    next_deliver_seq: u64,
+    pending_ack: bool,
+}
+
+/// Reliable, in-order delivery layered on top of raw UDP datagrams. Fragments
+/// oversized messages, retransmits unacked fragments with an exponential
+/// backoff timer, and reassembles/acks on the receive side. One instance per
+/// logical connection (shares the listener's socket, or owns the connector's).
+struct ReliableUdpChannel {
+    conn_id: u32,
+    peer_addr: SocketAddr,
+    socket: Arc<UdpSocket>,
+    send_state: Mutex<RelSendState>,
+    recv_state: Mutex<RelRecvState>,
+    deliver: Mutex<Pin<Box<dyn DatagramSink>>>,
+    // Cancelled once the connection is torn down (retries exhausted, a fatal
+    // maintenance error, or a reassembly overflow), so every task sharing
+    // this channel -- sender, maintenance loop, stream consumer -- notices
+    // and stops instead of continuing to queue work for a dead peer.
+    cancel: CancellationToken,
+    // Same PSK-derived cipher the plain `Data` path uses, applied to each
+    // `RelData` fragment's payload before fragmentation-aware framing. `None`
+    // for unencrypted (no-PSK) reliable tunnels.
+    cipher: Option<Arc<CipherState>>,
+}
+
+impl ReliableUdpChannel {
+    fn new(
+        socket: Arc<UdpSocket>,
+        peer_addr: SocketAddr,
+        conn_id: u32,
+        deliver: Pin<Box<dyn DatagramSink>>,
+        cipher: Option<Arc<CipherState>>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            conn_id,
+            peer_addr,
+            socket,
+            send_state: Mutex::new(RelSendState::default()),
+            recv_state: Mutex::new(RelRecvState::default()),
+            deliver: Mutex::new(deliver),
+            cancel: CancellationToken::new(),
+            cipher,
+        })
+    }
+
+    /// Marks the connection dead: further sends/receives are rejected and
+    /// every task racing `self.cancel.cancelled()` wakes up and tears down.
+    fn mark_dead(&self) {
+        self.cancel.cancel();
+    }
+
+    async fn send_message(&self, data: Bytes) -> Result<(), super::TunnelError> {
+        if self.cancel.is_cancelled() {
+            return Err(super::TunnelError::CommonError(
+                "udp reliable channel is closed".to_owned(),
+            ));
+        }
+
+        // with a cipher, each fragment also carries a 12-byte nonce and a
+        // 16-byte AEAD tag, which `UDP_REL_HEADER_OVERHEAD` does not cover.
+        let chunk_size = if self.cipher.is_some() {
+            UDP_REL_DATA_MTU - 12 - 16
+        } else {
+            UDP_REL_DATA_MTU
+        };
+        let chunks: Vec<&[u8]> = if data.is_empty() {
+            vec![&data[..]]
+        } else {
+            data.chunks(chunk_size).collect()
+        };
+        let frag_cnt = chunks.len() as u16;
+
+        let mut send_state = self.send_state.lock().await;
+        let base_seq = send_state.next_seq;
+        send_state.next_seq += frag_cnt as u64;
+
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let seq = base_seq + idx as u64;
+            let frag_bytes = if let Some(cipher) = &self.cipher {
+                let Some((nonce, ciphertext)) = cipher.encrypt(self.conn_id, chunk) else {
+                    drop(send_state);
+                    self.mark_dead();
+                    return Err(super::TunnelError::CommonError(
+                        "udp cipher nonce counter exhausted, connection must be re-established"
+                            .to_owned(),
+                    ));
+                };
+                let mut framed = Vec::with_capacity(12 + ciphertext.len());
+                framed.extend_from_slice(&nonce);
+                framed.extend_from_slice(&ciphertext);
+                framed
+            } else {
+                chunk.to_vec()
+            };
+            let packet =
+                UdpPacket::new_rel_data_packet(self.conn_id, seq, idx as u16, frag_cnt, frag_bytes);
+            let buf = encode_to_bytes::<_, UDP_DATA_MTU>(&packet);
+            self.socket.send_to(&buf, self.peer_addr).await?;
+            send_state.unacked.insert(
+                seq,
+                RelInFlight {
+                    packet: buf,
+                    sent_at: Instant::now(),
+                    rto: REL_RTO_INIT,
+                    retries: 0,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn on_ack(&self, seq: u64, recv_window: &[u64]) {
+        let mut send_state = self.send_state.lock().await;
+        send_state
+            .unacked
+            .retain(|k, _| (seq == u64::MAX || *k > seq) && !recv_window.contains(k));
+    }
+
+    /// Scans the unacked map for expired fragments and retransmits them.
+    /// Returns `false` once a fragment exceeds `REL_MAX_RETRIES`, meaning the
+    /// connection must be torn down rather than retried forever.
+    async fn maintenance_tick(&self) -> Result<bool, super::TunnelError> {
+        let now = Instant::now();
+        let mut to_resend = vec![];
+        {
+            let mut send_state = self.send_state.lock().await;
+            for (seq, inflight) in send_state.unacked.iter_mut() {
+                if now.duration_since(inflight.sent_at) < inflight.rto {
+                    continue;
+                }
+                if inflight.retries >= REL_MAX_RETRIES {
+                    tracing::warn!(?seq, ?self.peer_addr, "udp reliable fragment exhausted retries");
+                    return Ok(false);
+                }
+                inflight.retries += 1;
+                inflight.rto = (inflight.rto * 2).min(REL_RTO_MAX);
+                inflight.sent_at = now;
+                to_resend.push(inflight.packet.clone());
+            }
+        }
+        for packet in to_resend {
+            self.socket.send_to(&packet, self.peer_addr).await?;
+        }
+        self.flush_ack().await?;
+        Ok(true)
+    }
+
+    /// Feeds a decoded, conn_id-gated reliable payload into the channel.
+    async fn on_packet(&self, payload: RelRecv) -> Result<(), super::TunnelError> {
+        match payload {
+            RelRecv::Ack { seq, recv_window } => {
+                self.on_ack(seq, &recv_window).await;
+                Ok(())
+            }
+            RelRecv::Data {
+                seq,
+                frag_idx,
+                frag_cnt,
+                bytes,
+            } => self.on_data(seq, frag_idx, frag_cnt, bytes).await,
+        }
+    }
+
+    async fn on_data(
+        &self,
+        seq: u64,
+        frag_idx: u16,
+        frag_cnt: u16,
+        bytes: Vec<u8>,
+    ) -> Result<(), super::TunnelError> {
+        let bytes = if let Some(cipher) = &self.cipher {
+            if bytes.len() < 12 {
+                tracing::warn!(?self.peer_addr, "udp reliable fragment too short to carry a nonce, dropping");
+                return Ok(());
+            }
+            let (nonce, ciphertext) = bytes.split_at(12);
+            let Some(plaintext) = cipher.decrypt(self.conn_id, nonce.try_into().unwrap(), ciphertext)
+            else {
+                tracing::warn!(
+                    ?self.peer_addr,
+                    "udp reliable fragment failed AEAD authentication, dropping"
+                );
+                return Ok(());
+            };
+            plaintext
+        } else {
+            bytes
+        };
+
+        let completed = {
+            let mut recv_state = self.recv_state.lock().await;
+            recv_state.pending_ack = true;
+
+            if seq < recv_state.next_deliver_seq {
+                // already delivered, likely a retransmit racing our ack.
+                vec![]
+            } else if recv_state.reassembly.len() >= REL_MAX_REASSEMBLY_FRAGMENTS {
+                tracing::warn!(
+                    ?self.peer_addr,
+                    "udp reliable reassembly buffer full, dropping connection"
+                );
+                self.mark_dead();
+                return Err(super::TunnelError::CommonError(
+                    "udp reliable reassembly buffer overflow".to_owned(),
+                ));
+            } else {
+                recv_state
+                    .reassembly
+                    .insert(seq, (frag_idx, frag_cnt, bytes));
+
+                let mut completed = vec![];
+                loop {
+                    let base = recv_state.next_deliver_seq;
+                    let Some((_, frag_cnt, _)) = recv_state.reassembly.get(&base) else {
+                        break;
+                    };
+                    let frag_cnt = *frag_cnt;
+                    if !(0..frag_cnt as u64)
+                        .all(|i| recv_state.reassembly.contains_key(&(base + i)))
+                    {
+                        break;
+                    }
+                    let mut msg = Vec::new();
+                    for i in 0..frag_cnt as u64 {
+                        let (_, _, bytes) = recv_state.reassembly.remove(&(base + i)).unwrap();
+                        msg.extend_from_slice(&bytes);
+                    }
+                    recv_state.next_deliver_seq = base + frag_cnt as u64;
+                    completed.push(msg);
+                }
+                completed
+            }
+        };
+
+        let mut deliver = self.deliver.lock().await;
+        for msg in completed {
+            deliver.send(Bytes::from(msg)).await?;
+        }
+        Ok(())
+    }
+
+    async fn flush_ack(&self) -> Result<(), super::TunnelError> {
+        let (cumulative, recv_window) = {
+            let mut recv_state = self.recv_state.lock().await;
+            if !recv_state.pending_ack {
+                return Ok(());
+            }
+            recv_state.pending_ack = false;
+            (
+                // nothing delivered yet: u64::MAX, not 0, so seq 0 isn't acked
+                recv_state.next_deliver_seq.checked_sub(1).unwrap_or(u64::MAX),
+                recv_state.reassembly.keys().copied().collect::<Vec<_>>(),
+            )
+        };
+        let packet = UdpPacket::new_rel_ack_packet(self.conn_id, cumulative, recv_window);
+        let buf = encode_to_bytes::<_, UDP_DATA_MTU>(&packet);
+        self.socket.send_to(&buf, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// Sink side: writing a message here fragments/sequences/retransmits it
+    /// to `peer_addr` until acked.
+    fn pin_sink(self: Arc<Self>) -> Pin<Box<dyn DatagramSink>> {
+        Box::pin(futures::sink::drain().sink_map_err(|_: std::convert::Infallible| {
+            super::TunnelError::CommonError("udp reliable sink error".to_owned())
+        }).with(move |v: Bytes| {
+            let chan = self.clone();
+            async move { chan.send_message(v).await }
+        }))
+    }
+
+    /// Background maintenance loop (retransmits + ack coalescing). Returned
+    /// as a plain future so callers can run it on whichever task-tracking
+    /// mechanism they already use for the rest of the connection's tasks.
+    async fn maintenance_loop(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(REL_MAINTENANCE_INTERVAL).await;
+            match self.maintenance_tick().await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::warn!(?self.peer_addr, "udp reliable channel giving up, closing");
+                    self.mark_dead();
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(?e, ?self.peer_addr, "udp reliable maintenance error");
+                    self.mark_dead();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+struct PunchWaiter {
+    confirmed_tx: tokio::sync::mpsc::UnboundedSender<(SocketAddr, Instant)>,
+}
+
+/// Reacts to an inbound `HolePunch` packet on a shared socket. If `token`
+/// matches one of our own in-progress `UdpHolePuncher::punch` attempts, that
+/// attempt is notified of the candidate that got through. Otherwise this is
+/// (presumably) the peer's own punch burst reaching us first, so the token
+/// is echoed straight back to `addr` -- once it loops back to the peer's
+/// puncher, their attempt confirms the same way ours would.
+async fn handle_hole_punch(
+    waiters: &DashMap<u64, PunchWaiter>,
+    socket: &UdpSocket,
+    addr: SocketAddr,
+    token_bytes: &[u8],
+) {
+    let Ok(token_bytes) = <[u8; 8]>::try_from(token_bytes) else {
+        tracing::warn!(?token_bytes, "udp hole punch token has unexpected length");
+        return;
+    };
+    let token = u64::from_be_bytes(token_bytes);
+
+    if let Some(waiter) = waiters.get(&token) {
+        let _ = waiter.confirmed_tx.send((addr, Instant::now()));
+        return;
+    }
+
+    tracing::trace!(?token, ?addr, "udp hole punch echoing unknown token back");
+    let pkt = UdpPacket::new_hole_punch_packet(token_bytes.to_vec());
+    let buf = encode_to_bytes::<_, UDP_DATA_MTU>(&pkt);
+    if let Err(e) = socket.send_to(&buf, addr).await {
+        tracing::warn!(?e, ?addr, "udp hole punch echo send failed");
+    }
+}
+
+/// Drives NAT hole punching for a single candidate set: fires a punch
+/// burst at every candidate while relying on the owning socket's receive
+/// loop (see `handle_hole_punch`) to notice echoes and wake us up. Meant to
+/// be run against the same socket a `UdpTunnelListener` is bound to, so a
+/// successful punch can be promoted straight into the normal `Syn`/`Sack`
+/// handshake without rebinding.
+pub struct UdpHolePuncher {
+    socket: Arc<UdpSocket>,
+    waiters: Arc<DashMap<u64, PunchWaiter>>,
+}
+
+impl UdpHolePuncher {
+    pub fn new(socket: Arc<UdpSocket>, waiters: Arc<DashMap<u64, PunchWaiter>>) -> Self {
+        Self { socket, waiters }
+    }
+
+    /// Punches every candidate concurrently and returns the address that
+    /// confirmed first (lowest RTT), after giving slower candidates a short
+    /// grace period to also land. Ties are broken in favor of whichever
+    /// candidate's echo arrived first.
+    pub async fn punch(
+        &self,
+        candidates: Vec<SocketAddr>,
+    ) -> Result<SocketAddr, super::TunnelError> {
+        if candidates.is_empty() {
+            return Err(super::TunnelError::ConnectError(
+                "udp hole punch: no candidates given".to_owned(),
+            ));
+        }
+
+        let token: u64 = rand::random();
+        let (confirmed_tx, mut confirmed_rx) = tokio::sync::mpsc::unbounded_channel();
+        self.waiters.insert(token, PunchWaiter { confirmed_tx });
+
+        let token_bytes = token.to_be_bytes().to_vec();
+        let burst_socket = self.socket.clone();
+        let burst_candidates = candidates.clone();
+        let burst = tokio::spawn(async move {
+            let pkt = UdpPacket::new_hole_punch_packet(token_bytes);
+            let buf = encode_to_bytes::<_, UDP_DATA_MTU>(&pkt);
+            for _ in 0..HOLE_PUNCH_BURST_COUNT {
+                for addr in &burst_candidates {
+                    if let Err(e) = burst_socket.send_to(&buf, addr).await {
+                        tracing::warn!(?e, ?addr, "udp hole punch burst send failed");
+                    }
+                }
+                tokio::time::sleep(HOLE_PUNCH_BURST_INTERVAL).await;
+            }
+        });
+
+        let start = Instant::now();
+        let mut confirmed: Vec<(SocketAddr, Instant)> = vec![];
+        let deadline = tokio::time::sleep(HOLE_PUNCH_BURST_INTERVAL * HOLE_PUNCH_BURST_COUNT);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                _ = &mut deadline => break,
+                maybe = confirmed_rx.recv() => {
+                    let Some(hit) = maybe else { break };
+                    let first = confirmed.is_empty();
+                    confirmed.push(hit);
+                    if first {
+                        // a candidate got through; give the rest a short grace
+                        // period in case a lower-RTT path is still in flight.
+                        deadline
+                            .as_mut()
+                            .reset(tokio::time::Instant::now() + HOLE_PUNCH_GRACE_PERIOD);
+                    }
+                }
+            }
+        }
+
+        burst.abort();
+        // abort() only requests cancellation; wait for the task to actually
+        // unwind so its `burst_socket` Arc clone is guaranteed dropped before
+        // we return, or callers doing Arc::try_unwrap on the socket race it.
+        let _ = burst.await;
+        self.waiters.remove(&token);
+
+        let Some((addr, _)) = confirmed.into_iter().min_by_key(|(_, at)| *at - start) else {
+            return Err(super::TunnelError::ConnectError(
+                "udp hole punch: no candidate confirmed before timeout".to_owned(),
+            ));
+        };
+        Ok(addr)
+    }
+}
+
+/// Punches through NAT to whichever of `candidates` answers first, then
+/// immediately promotes that confirmed address into the normal `udp://`
+/// Syn/Sack handshake over the *same* socket the punch used -- punching
+/// with one socket and connecting with another would leave the connect
+/// attempt looking, to each side's NAT, like an unrelated, still-unopened
+/// flow. Candidate discovery itself (exchanging `candidates` with the
+/// remote peer) is out of scope here; callers are expected to source it
+/// from their own peer-discovery channel.
+pub async fn connect_after_hole_punch(
+    socket: UdpSocket,
+    candidates: Vec<SocketAddr>,
+    reliable: Option<bool>,
+    psk: Option<Vec<u8>>,
+) -> Result<Box<dyn super::Tunnel>, super::TunnelError> {
+    let socket = Arc::new(socket);
+    let waiters: Arc<DashMap<u64, PunchWaiter>> = Arc::new(DashMap::new());
+
+    let dispatch_socket = socket.clone();
+    let dispatch_waiters = waiters.clone();
+    let dispatch = tokio::spawn(async move {
+        let mut buf = BytesMut::new();
+        loop {
+            buf.resize(UDP_DATA_MTU, 0);
+            let Ok((size, addr)) = dispatch_socket.recv_from(&mut buf).await else {
+                break;
+            };
+            let pkt = buf.split_to(size);
+            let Ok(udp_packet) = rkyv_util::decode_from_bytes_checked::<UdpPacket>(&pkt) else {
+                continue;
+            };
+            if let ArchivedUdpPacketPayload::HolePunch(token_bytes) = udp_packet.payload {
+                handle_hole_punch(
+                    dispatch_waiters.as_ref(),
+                    dispatch_socket.as_ref(),
+                    addr,
+                    token_bytes.as_slice(),
+                )
+                .await;
+            }
+        }
+    });
+
+    let puncher = UdpHolePuncher::new(socket.clone(), waiters);
+    let confirmed = puncher.punch(candidates).await;
+    drop(puncher);
+
+    dispatch.abort();
+    let _ = dispatch.await;
+    let confirmed = confirmed?;
+
+    let scheme = if reliable.unwrap_or(false) {
+        UDP_RELIABLE_SCHEME
+    } else {
+        "udp"
+    };
+    let mut connector =
+        UdpTunnelConnector::new(build_url_from_socket_addr(&confirmed.to_string(), scheme));
+    if let Some(reliable) = reliable {
+        connector.set_reliable(reliable);
+    }
+    if let Some(psk) = psk {
+        connector.set_psk(psk);
+    }
+
+    let socket = Arc::try_unwrap(socket).map_err(|_| {
+        super::TunnelError::CommonError(
+            "udp hole punch socket still has outstanding references".to_owned(),
+        )
+    })?;
+    connector.try_connect_with_socket(socket).await
+}
+
+fn get_tunnel_from_socket<C: Codec>(
     socket: Arc<UdpSocket>,
     addr: SocketAddr,
     conn_id: u32,
+    cipher: Option<Arc<CipherState>>,
+    codec: C,
 ) -> Box<dyn super::Tunnel> {
-    let udp = UdpFramed::new(socket.clone(), BytesCodec::new(UDP_DATA_MTU));
+    let udp = UdpFramed::new(socket.clone(), codec);
     let (sink, stream) = udp.split();
 
     let recv_addr = addr;
-    let stream = stream.filter_map(move |v| async move {
-        tracing::trace!(?v, "udp stream recv something");
-        if v.is_err() {
-            tracing::warn!(?v, "udp stream error");
-            return Some(Err(super::TunnelError::CommonError(
-                "udp stream error".to_owned(),
-            )));
-        }
+    let recv_cipher = cipher.clone();
+    let stream = stream.filter_map(move |v| {
+        let recv_cipher = recv_cipher.clone();
+        async move {
+            tracing::trace!(?v, "udp stream recv something");
+            if v.is_err() {
+                tracing::warn!(?v, "udp stream error");
+                return Some(Err(super::TunnelError::CommonError(
+                    "udp stream error".to_owned(),
+                )));
+            }
 
-        let (buf, addr) = v.unwrap();
-        assert_eq!(addr, recv_addr.clone());
-        Some(Ok(try_get_data_payload(buf, conn_id.clone())?))
+            let (buf, addr) = v.unwrap();
+            assert_eq!(addr, recv_addr.clone());
+            Some(Ok(try_get_data_payload(
+                buf,
+                conn_id.clone(),
+                recv_cipher.as_deref(),
+            )?))
+        }
     });
     let stream = Box::pin(stream);
 
     let sender_addr = addr;
-    let sink = Box::pin(sink.with(move |v: Bytes| async move {
-        if false {
-            return Err(super::TunnelError::CommonError("udp sink error".to_owned()));
+    let sink = Box::pin(sink.with(move |v: Bytes| {
+        let cipher = cipher.clone();
+        async move {
+            // TODO: two copy here, how to avoid?
+            let udp_packet = if let Some(cipher) = &cipher {
+                let Some((nonce, ciphertext)) = cipher.encrypt(conn_id, &v) else {
+                    return Err(super::TunnelError::CommonError(
+                        "udp cipher nonce counter exhausted, connection must be re-established"
+                            .to_owned(),
+                    ));
+                };
+                UdpPacket::new_encrypted_data_packet(conn_id, nonce, ciphertext)
+            } else {
+                UdpPacket::new_data_packet(conn_id, v.to_vec())
+            };
+            tracing::trace!(?udp_packet, ?v, "udp send packet");
+            let v = encode_to_bytes::<_, UDP_DATA_MTU>(&udp_packet);
+
+            Ok((v, sender_addr))
         }
-
-        // TODO: two copy here, how to avoid?
-        let udp_packet = UdpPacket::new_data_packet(conn_id, v.to_vec());
-        tracing::trace!(?udp_packet, ?v, "udp send packet");
-        let v = encode_to_bytes::<_, UDP_DATA_MTU>(&udp_packet);
-
-        Ok((v, sender_addr))
     }));
 
     FramedTunnel::new_tunnel_with_info(
@@ -156,19 +987,120 @@ fn get_tunnel_from_socket(
     )
 }
 
+/// Like `get_tunnel_from_socket`, but the `conn_id` exchanged over `socket`
+/// uses reliable mode: the returned tunnel's stream/sink are backed by a
+/// `ReliableUdpChannel` instead of a raw `UdpFramed`. The socket is assumed
+/// to be owned exclusively by this connection (true for the connector side;
+/// the listener routes packets in via its own shared recv loop instead).
+fn get_reliable_tunnel_from_socket(
+    socket: Arc<UdpSocket>,
+    addr: SocketAddr,
+    conn_id: u32,
+    cipher: Option<Arc<CipherState>>,
+) -> Box<dyn super::Tunnel> {
+    let (deliver_tx, deliver_rx) = tokio::sync::mpsc::channel::<Bytes>(256);
+    let deliver_sink: Pin<Box<dyn DatagramSink>> = Box::pin(futures::sink::unfold(
+        deliver_tx,
+        |tx: tokio::sync::mpsc::Sender<Bytes>, item: Bytes| async move {
+            tx.send(item).await.map_err(|_| {
+                super::TunnelError::CommonError("udp reliable deliver channel closed".to_owned())
+            })?;
+            Ok::<_, super::TunnelError>(tx)
+        },
+    ));
+
+    let chan = ReliableUdpChannel::new(socket.clone(), addr, conn_id, deliver_sink, cipher);
+
+    let recv_socket = socket.clone();
+    let recv_chan = chan.clone();
+    let maint_chan = chan.clone();
+    tokio::spawn(async move {
+        let recv_fut = async {
+            let mut buf = BytesMut::new();
+            loop {
+                buf.resize(UDP_DATA_MTU, 0);
+                let Ok((size, recv_addr)) = recv_socket.recv_from(&mut buf).await else {
+                    break;
+                };
+                if recv_addr != addr {
+                    continue;
+                }
+                let pkt = buf.split_to(size);
+                let Some(payload) = try_get_rel_payload(&pkt, conn_id) else {
+                    continue;
+                };
+                if let Err(e) = recv_chan.on_packet(payload).await {
+                    tracing::warn!(?e, ?addr, "udp reliable connector on_packet failed, dropping connection");
+                    break;
+                }
+            }
+        };
+        // same reasoning as the listener side: either direction dying ends
+        // the whole connection, so the other task-equivalent must stop too.
+        tokio::select! {
+            _ = recv_fut => {}
+            _ = maint_chan.maintenance_loop() => {}
+            _ = maint_chan.cancel.cancelled() => {}
+        }
+        maint_chan.mark_dead();
+    });
+
+    let stream_cancel = chan.cancel.clone();
+    let stream: Pin<Box<dyn DatagramStream>> = Box::pin(futures::stream::unfold(
+        deliver_rx,
+        move |mut rx| {
+            let cancel = stream_cancel.clone();
+            async move {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                tokio::select! {
+                    v = rx.recv() => v.map(|v| (Ok(BytesMut::from(&v[..])), rx)),
+                    _ = cancel.cancelled() => None,
+                }
+            }
+        },
+    ));
+
+    FramedTunnel::new_tunnel_with_info(
+        stream,
+        chan.pin_sink(),
+        super::TunnelInfo {
+            tunnel_type: "udp+rel".to_owned(),
+            local_addr: super::build_url_from_socket_addr(
+                &socket.local_addr().unwrap().to_string(),
+                "udp+rel",
+            )
+            .into(),
+            remote_addr: super::build_url_from_socket_addr(&addr.to_string(), "udp+rel").into(),
+        },
+    )
+}
+
 struct StreamSinkPair(
     Pin<Box<dyn DatagramStream>>,
     Pin<Box<dyn DatagramSink>>,
     u32,
+    Option<Arc<CipherState>>,
 );
 type ArcStreamSinkPair = Arc<Mutex<StreamSinkPair>>;
 
+// A connection is either plain (datagrams forwarded straight into the ring
+// tunnel) or reliable (routed through a `ReliableUdpChannel` for
+// fragmentation/retransmit/reassembly first).
+enum ConnSlot {
+    Plain(ArcStreamSinkPair),
+    Reliable(Arc<ReliableUdpChannel>),
+}
+
 pub struct UdpTunnelListener {
     addr: url::Url,
     socket: Option<Arc<UdpSocket>>,
+    psk: Option<Arc<Vec<u8>>>,
 
-    sock_map: Arc<DashMap<SocketAddr, ArcStreamSinkPair>>,
+    sock_map: Arc<DashMap<SocketAddr, ConnSlot>>,
     forward_tasks: Arc<Mutex<JoinSet<()>>>,
+    hole_punch_waiters: Arc<DashMap<u64, PunchWaiter>>,
 
     conn_recv: tokio::sync::mpsc::Receiver<Box<dyn Tunnel>>,
     conn_send: Option<tokio::sync::mpsc::Sender<Box<dyn Tunnel>>>,
@@ -180,15 +1112,35 @@ impl UdpTunnelListener {
         Self {
             addr,
             socket: None,
+            psk: None,
             sock_map: Arc::new(DashMap::new()),
             forward_tasks: Arc::new(Mutex::new(JoinSet::new())),
+            hole_punch_waiters: Arc::new(DashMap::new()),
             conn_recv,
             conn_send: Some(conn_send),
         }
     }
 
+    /// Require every accepted connection to be encrypted with this
+    /// pre-shared key (ChaCha20-Poly1305, keys derived per-connection via
+    /// HKDF). Unset by default, in which case tunnels stay unencrypted.
+    pub fn set_psk(&mut self, psk: Vec<u8>) {
+        self.psk = Some(Arc::new(psk));
+    }
+
+    /// Builds a hole puncher sharing this listener's bound socket, so a
+    /// candidate confirmed by punching can be handed straight to the normal
+    /// `Syn`/`Sack` handshake without rebinding. Only available once
+    /// `listen()` has run and a socket is bound.
+    pub fn get_hole_puncher(&self) -> Option<UdpHolePuncher> {
+        Some(UdpHolePuncher::new(
+            self.socket.clone()?,
+            self.hole_punch_waiters.clone(),
+        ))
+    }
+
     async fn try_forward_packet(
-        sock_map: &DashMap<SocketAddr, ArcStreamSinkPair>,
+        sock_map: &DashMap<SocketAddr, ConnSlot>,
         buf: BytesMut,
         addr: SocketAddr,
     ) -> Result<(), super::TunnelError> {
@@ -200,13 +1152,29 @@ impl UdpTunnelListener {
 
         log::trace!("udp forward packet: {:?}, {:?}", addr, buf);
         let entry = entry.unwrap();
-        let pair = entry.value().clone();
+        let slot = match entry.value() {
+            ConnSlot::Plain(pair) => ConnSlot::Plain(pair.clone()),
+            ConnSlot::Reliable(chan) => ConnSlot::Reliable(chan.clone()),
+        };
         drop(entry);
 
-        let Some(buf) = try_get_data_payload(buf, pair.lock().await.2) else {
-            return Ok(());
-        };
-        pair.lock().await.1.send(buf.freeze()).await?;
+        match slot {
+            ConnSlot::Plain(pair) => {
+                let guard = pair.lock().await;
+                let (conn_id, cipher) = (guard.2, guard.3.clone());
+                drop(guard);
+                let Some(buf) = try_get_data_payload(buf, conn_id, cipher.as_deref()) else {
+                    return Ok(());
+                };
+                pair.lock().await.1.send(buf.freeze()).await?;
+            }
+            ConnSlot::Reliable(chan) => {
+                let Some(payload) = try_get_rel_payload(&buf, chan.conn_id) else {
+                    return Ok(());
+                };
+                chan.on_packet(payload).await?;
+            }
+        }
         Ok(())
     }
 
@@ -214,46 +1182,115 @@ impl UdpTunnelListener {
         socket: Arc<UdpSocket>,
         addr: SocketAddr,
         forward_tasks: Arc<Mutex<JoinSet<()>>>,
-        sock_map: Arc<DashMap<SocketAddr, ArcStreamSinkPair>>,
+        sock_map: Arc<DashMap<SocketAddr, ConnSlot>>,
         local_url: url::Url,
         conn_id: u32,
+        reliable: bool,
+        client_salt: u32,
+        psk: Option<Arc<Vec<u8>>>,
     ) -> Result<Box<dyn Tunnel>, super::TunnelError> {
-        tracing::info!(?conn_id, ?addr, "udp connection accept handling",);
+        tracing::info!(?conn_id, ?addr, ?reliable, "udp connection accept handling",);
+
+        let server_salt: u32 = rand::random();
+        let cipher = psk
+            .as_ref()
+            .map(|psk| Arc::new(CipherState::new_server(psk, client_salt, server_salt)));
 
-        let udp_packet = UdpPacket::new_sack_packet(conn_id);
+        let udp_packet = UdpPacket::new_sack_packet(conn_id, server_salt);
         let sack_buf = encode_to_bytes::<_, UDP_DATA_MTU>(&udp_packet);
         socket.send_to(&sack_buf, addr).await?;
 
         let (ctunnel, stunnel) = create_ring_tunnel_pair();
-        let udp_tunnel = get_tunnel_from_socket(socket.clone(), addr, conn_id);
-        let ss_pair = StreamSinkPair(ctunnel.pin_stream(), ctunnel.pin_sink(), conn_id);
         let addr_copy = addr.clone();
-        sock_map.insert(addr, Arc::new(Mutex::new(ss_pair)));
         let ctunnel_stream = ctunnel.pin_stream();
-        forward_tasks.lock().await.spawn(async move {
-            let ret = ctunnel_stream
-                .map(|v| {
-                    tracing::trace!(?v, "udp stream recv something in forward task");
-                    if v.is_err() {
-                        return Err(super::TunnelError::CommonError(
-                            "udp stream error".to_owned(),
-                        ));
-                    }
-                    Ok(v.unwrap().freeze())
-                })
-                .forward(udp_tunnel.pin_sink())
-                .await;
-            if let None = sock_map.remove(&addr_copy) {
-                log::warn!("udp forward packet: {:?}, no entry", addr_copy);
-            }
-            close_tunnel(&ctunnel).await.unwrap();
-            log::warn!("udp connection forward done: {:?}, {:?}", addr_copy, ret);
-        });
+
+        let tunnel_type = if reliable { "udp+rel" } else { "udp" };
+        if reliable {
+            let chan = ReliableUdpChannel::new(
+                socket.clone(),
+                addr,
+                conn_id,
+                ctunnel.pin_sink(),
+                cipher.clone(),
+            );
+            sock_map.insert(addr, ConnSlot::Reliable(chan.clone()));
+            let out_sink = chan.pin_sink();
+            let maint_chan = chan.clone();
+            forward_tasks.lock().await.spawn(async move {
+                let forward_fut = ctunnel_stream
+                    .map(|v| {
+                        tracing::trace!(?v, "udp reliable stream recv something in forward task");
+                        if v.is_err() {
+                            return Err(super::TunnelError::CommonError(
+                                "udp stream error".to_owned(),
+                            ));
+                        }
+                        Ok(v.unwrap().freeze())
+                    })
+                    .forward(out_sink);
+                tokio::pin!(forward_fut);
+
+                // whichever gives up first -- the ring tunnel side closing,
+                // the reliable channel exhausting retries, or some other
+                // per-packet failure (e.g. a reassembly overflow) marking the
+                // channel dead -- tears the whole connection down; none of
+                // these may outlive the others.
+                let ret = tokio::select! {
+                    ret = &mut forward_fut => ret,
+                    _ = maint_chan.maintenance_loop() => Err(super::TunnelError::CommonError(
+                        "udp reliable channel exhausted retries".to_owned(),
+                    )),
+                    _ = maint_chan.cancel.cancelled() => Err(super::TunnelError::CommonError(
+                        "udp reliable channel marked dead".to_owned(),
+                    )),
+                };
+
+                if let None = sock_map.remove(&addr_copy) {
+                    log::warn!("udp forward packet: {:?}, no entry", addr_copy);
+                }
+                close_tunnel(&ctunnel).await.unwrap();
+                log::warn!(
+                    "udp reliable connection forward done: {:?}, {:?}",
+                    addr_copy,
+                    ret
+                );
+            });
+        } else {
+            let udp_tunnel = get_tunnel_from_socket(
+                socket.clone(),
+                addr,
+                conn_id,
+                cipher.clone(),
+                BytesCodec::new(UDP_DATA_MTU),
+            );
+            let ss_pair =
+                StreamSinkPair(ctunnel.pin_stream(), ctunnel.pin_sink(), conn_id, cipher);
+            sock_map.insert(addr, ConnSlot::Plain(Arc::new(Mutex::new(ss_pair))));
+            forward_tasks.lock().await.spawn(async move {
+                let ret = ctunnel_stream
+                    .map(|v| {
+                        tracing::trace!(?v, "udp stream recv something in forward task");
+                        if v.is_err() {
+                            return Err(super::TunnelError::CommonError(
+                                "udp stream error".to_owned(),
+                            ));
+                        }
+                        Ok(v.unwrap().freeze())
+                    })
+                    .forward(udp_tunnel.pin_sink())
+                    .await;
+                if let None = sock_map.remove(&addr_copy) {
+                    log::warn!("udp forward packet: {:?}, no entry", addr_copy);
+                }
+                close_tunnel(&ctunnel).await.unwrap();
+                log::warn!("udp connection forward done: {:?}, {:?}", addr_copy, ret);
+            });
+        }
 
         Ok(Box::new(TunnelWithCustomInfo::new(
             stunnel,
             TunnelInfo {
-                tunnel_type: "udp".to_owned(),
+                tunnel_type: tunnel_type.to_owned(),
                 local_addr: local_url.into(),
                 remote_addr: build_url_from_socket_addr(&addr.to_string(), "udp").into(),
             },
@@ -268,7 +1305,11 @@ impl UdpTunnelListener {
 #[async_trait]
 impl TunnelListener for UdpTunnelListener {
     async fn listen(&mut self) -> Result<(), super::TunnelError> {
-        let addr = super::check_scheme_and_get_socket_addr::<SocketAddr>(&self.addr, "udp")?;
+        let reliable = self.addr.scheme() == UDP_RELIABLE_SCHEME;
+        let addr = super::check_scheme_and_get_socket_addr::<SocketAddr>(
+            &self.addr,
+            self.addr.scheme(),
+        )?;
 
         let socket2_socket = socket2::Socket::new(
             socket2::Domain::for_address(addr),
@@ -283,6 +1324,8 @@ impl TunnelListener for UdpTunnelListener {
         let sock_map = self.sock_map.clone();
         let conn_send = self.conn_send.take().unwrap();
         let local_url = self.local_url().clone();
+        let psk = self.psk.clone();
+        let hole_punch_waiters = self.hole_punch_waiters.clone();
         self.forward_tasks.lock().await.spawn(
             async move {
                 loop {
@@ -303,7 +1346,7 @@ impl TunnelListener for UdpTunnelListener {
                         continue;
                     };
 
-                    if matches!(udp_packet.payload, ArchivedUdpPacketPayload::Syn) {
+                    if let ArchivedUdpPacketPayload::Syn(client_salt) = udp_packet.payload {
                         let conn = Self::handle_connect(
                             socket.clone(),
                             addr,
@@ -311,16 +1354,33 @@ impl TunnelListener for UdpTunnelListener {
                             sock_map.clone(),
                             local_url.clone(),
                             udp_packet.conn_id.into(),
+                            reliable,
+                            client_salt.into(),
+                            psk.clone(),
                         )
                         .await
                         .unwrap();
                         if let Err(e) = conn_send.send(conn).await {
                             tracing::warn!(?e, "udp send conn to accept channel error");
                         }
-                    } else {
-                        Self::try_forward_packet(sock_map.as_ref(), buf, addr)
-                            .await
-                            .unwrap();
+                    } else if let ArchivedUdpPacketPayload::HolePunch(token_bytes) =
+                        udp_packet.payload
+                    {
+                        handle_hole_punch(
+                            hole_punch_waiters.as_ref(),
+                            socket.as_ref(),
+                            addr,
+                            token_bytes.as_slice(),
+                        )
+                        .await;
+                    } else if let Err(e) = Self::try_forward_packet(sock_map.as_ref(), buf, addr).await
+                    {
+                        // this is a per-connection failure (e.g. the reassembly
+                        // buffer overflow or a closed deliver sink) -- drop just
+                        // this connection's entry, never the shared forward
+                        // task that every other connection on this socket relies on.
+                        tracing::warn!(?e, ?addr, "udp forward packet failed, dropping connection");
+                        sock_map.remove(&addr);
                     }
                 }
             }
@@ -363,7 +1423,7 @@ impl TunnelListener for UdpTunnelListener {
 
     fn get_conn_counter(&self) -> Arc<Box<dyn TunnelConnCounter>> {
         struct UdpTunnelConnCounter {
-            sock_map: Arc<DashMap<SocketAddr, ArcStreamSinkPair>>,
+            sock_map: Arc<DashMap<SocketAddr, ConnSlot>>,
         }
 
         impl Debug for UdpTunnelConnCounter {
@@ -389,6 +1449,9 @@ impl TunnelListener for UdpTunnelListener {
 pub struct UdpTunnelConnector {
     addr: url::Url,
     bind_addrs: Vec<SocketAddr>,
+    // `None` derives reliability from the `udp+rel://` scheme; `Some` forces it.
+    reliable: Option<bool>,
+    psk: Option<Arc<Vec<u8>>>,
 }
 
 impl UdpTunnelConnector {
@@ -396,14 +1459,28 @@ impl UdpTunnelConnector {
         Self {
             addr,
             bind_addrs: vec![],
+            reliable: None,
+            psk: None,
         }
     }
 
+    /// Force reliable (ordered, retransmitted) delivery on or off regardless
+    /// of the connect URL's scheme.
+    pub fn set_reliable(&mut self, reliable: bool) {
+        self.reliable = Some(reliable);
+    }
+
+    /// Encrypt the tunnel with this pre-shared key (ChaCha20-Poly1305, keys
+    /// derived per-connection via HKDF). Must match the listener's PSK.
+    pub fn set_psk(&mut self, psk: Vec<u8>) {
+        self.psk = Some(Arc::new(psk));
+    }
+
     async fn wait_sack(
         socket: &UdpSocket,
         addr: SocketAddr,
         conn_id: u32,
-    ) -> Result<(), super::TunnelError> {
+    ) -> Result<u32, super::TunnelError> {
         let mut buf = BytesMut::new();
         buf.resize(128, 0);
 
@@ -437,54 +1514,86 @@ impl UdpTunnelConnector {
             )));
         }
 
-        if !matches!(udp_packet.payload, ArchivedUdpPacketPayload::Sack) {
+        let ArchivedUdpPacketPayload::Sack(server_salt) = udp_packet.payload else {
             return Err(super::TunnelError::ConnectError(format!(
                 "udp connect error, unexpected payload. payload: {:?}",
                 udp_packet.payload
             )));
-        }
+        };
 
-        Ok(())
+        Ok(server_salt.into())
     }
 
     async fn wait_sack_loop(
         socket: &UdpSocket,
         addr: SocketAddr,
         conn_id: u32,
-    ) -> Result<(), super::TunnelError> {
-        while let Err(err) = Self::wait_sack(socket, addr, conn_id).await {
-            tracing::warn!(?err, "udp wait sack error");
+    ) -> Result<u32, super::TunnelError> {
+        loop {
+            match Self::wait_sack(socket, addr, conn_id).await {
+                Ok(server_salt) => return Ok(server_salt),
+                Err(err) => tracing::warn!(?err, "udp wait sack error"),
+            }
         }
-        Ok(())
     }
 
     pub async fn try_connect_with_socket(
         &self,
         socket: UdpSocket,
     ) -> Result<Box<dyn super::Tunnel>, super::TunnelError> {
-        let addr = super::check_scheme_and_get_socket_addr::<SocketAddr>(&self.addr, "udp")?;
-        log::warn!("udp connect: {:?}", self.addr);
+        let reliable = self
+            .reliable
+            .unwrap_or(self.addr.scheme() == UDP_RELIABLE_SCHEME);
+        let addr = super::check_scheme_and_get_socket_addr::<SocketAddr>(
+            &self.addr,
+            self.addr.scheme(),
+        )?;
+        log::warn!("udp connect: {:?}, reliable: {:?}", self.addr, reliable);
 
         // send syn
         let conn_id = rand::random();
-        let udp_packet = UdpPacket::new_syn_packet(conn_id);
+        let client_salt: u32 = rand::random();
+        let udp_packet = UdpPacket::new_syn_packet(conn_id, client_salt);
         let b = encode_to_bytes::<_, UDP_DATA_MTU>(&udp_packet);
         let ret = socket.send_to(&b, &addr).await?;
         tracing::warn!(?udp_packet, ?ret, "udp send syn");
 
         // wait sack
-        tokio::time::timeout(
+        let server_salt = tokio::time::timeout(
             tokio::time::Duration::from_secs(3),
             Self::wait_sack_loop(&socket, addr, conn_id),
         )
         .await??;
 
+        let cipher = self
+            .psk
+            .as_ref()
+            .map(|psk| Arc::new(CipherState::new_client(psk, client_salt, server_salt)));
+
         // sack done
         let local_addr = socket.local_addr().unwrap().to_string();
+        let socket = Arc::new(socket);
+        let (tunnel, tunnel_type) = if reliable {
+            (
+                get_reliable_tunnel_from_socket(socket, addr, conn_id, cipher.clone()),
+                "udp+rel",
+            )
+        } else {
+            (
+                get_tunnel_from_socket(
+                    socket,
+                    addr,
+                    conn_id,
+                    cipher,
+                    BytesCodec::new(UDP_DATA_MTU),
+                ),
+                "udp",
+            )
+        };
         Ok(Box::new(TunnelWithCustomInfo::new(
-            get_tunnel_from_socket(Arc::new(socket), addr, conn_id),
+            tunnel,
             TunnelInfo {
-                tunnel_type: "udp".to_owned(),
+                tunnel_type: tunnel_type.to_owned(),
                 local_addr: super::build_url_from_socket_addr(&local_addr, "udp").into(),
                 remote_addr: self.remote_url().into(),
             },
@@ -570,6 +1679,114 @@ mod tests {
         _tunnel_pingpong(listener, connector).await
     }
 
+    #[tokio::test]
+    async fn udp_encrypted_pingpong() {
+        let mut listener = UdpTunnelListener::new("udp://0.0.0.0:5550".parse().unwrap());
+        listener.set_psk(b"a very secret psk".to_vec());
+        let mut connector = UdpTunnelConnector::new("udp://127.0.0.1:5550".parse().unwrap());
+        connector.set_psk(b"a very secret psk".to_vec());
+        _tunnel_pingpong(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn udp_reliable_pingpong() {
+        let listener = UdpTunnelListener::new("udp+rel://0.0.0.0:5552".parse().unwrap());
+        let connector = UdpTunnelConnector::new("udp+rel://127.0.0.1:5552".parse().unwrap());
+        _tunnel_pingpong(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn udp_reliable_bench() {
+        let listener = UdpTunnelListener::new("udp+rel://0.0.0.0:5551".parse().unwrap());
+        let connector = UdpTunnelConnector::new("udp+rel://127.0.0.1:5551".parse().unwrap());
+        _tunnel_bench(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn udp_rel_encrypted_pingpong() {
+        let mut listener = UdpTunnelListener::new("udp+rel://0.0.0.0:5549".parse().unwrap());
+        listener.set_psk(b"a very secret psk".to_vec());
+        let mut connector = UdpTunnelConnector::new("udp+rel://127.0.0.1:5549".parse().unwrap());
+        connector.set_psk(b"a very secret psk".to_vec());
+        _tunnel_pingpong(listener, connector).await
+    }
+
+    #[test]
+    fn udp_encrypted_data_replay_rejected() {
+        let client = CipherState::new_client(b"a very secret psk", 1, 2);
+        let server = CipherState::new_server(b"a very secret psk", 1, 2);
+
+        let (nonce, ciphertext) = client.encrypt(42, b"hello").unwrap();
+        assert_eq!(
+            server.decrypt_fresh(42, &nonce, &ciphertext).unwrap(),
+            b"hello"
+        );
+        // Replaying the exact same packet must be rejected even though it
+        // still passes AEAD authentication.
+        assert!(server.decrypt_fresh(42, &nonce, &ciphertext).is_none());
+
+        // A later, never-before-seen counter is still accepted.
+        let (nonce2, ciphertext2) = client.encrypt(42, b"world").unwrap();
+        assert_eq!(
+            server.decrypt_fresh(42, &nonce2, &ciphertext2).unwrap(),
+            b"world"
+        );
+        // And the first packet can't be replayed again after that either.
+        assert!(server.decrypt_fresh(42, &nonce, &ciphertext).is_none());
+    }
+
+    async fn hole_punch_dispatch_loop(
+        socket: Arc<UdpSocket>,
+        waiters: Arc<DashMap<u64, PunchWaiter>>,
+    ) {
+        let mut buf = BytesMut::new();
+        loop {
+            buf.resize(UDP_DATA_MTU, 0);
+            let Ok((size, addr)) = socket.recv_from(&mut buf).await else {
+                break;
+            };
+            let pkt = buf.split_to(size);
+            let Ok(udp_packet) = rkyv_util::decode_from_bytes_checked::<UdpPacket>(&pkt) else {
+                continue;
+            };
+            if let ArchivedUdpPacketPayload::HolePunch(token_bytes) = udp_packet.payload {
+                handle_hole_punch(waiters.as_ref(), socket.as_ref(), addr, token_bytes.as_slice())
+                    .await;
+            }
+        }
+    }
+
+    // Drives two `UdpHolePuncher`s against each other (simultaneous open):
+    // both sides punch at the same time, each racing its own burst timer
+    // against the other's, with neither acting as a pre-designated listener.
+    #[tokio::test]
+    async fn udp_hole_punch_simultaneous() {
+        let sock_a = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let sock_b = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let addr_a = sock_a.local_addr().unwrap();
+        let addr_b = sock_b.local_addr().unwrap();
+
+        let waiters_a: Arc<DashMap<u64, PunchWaiter>> = Arc::new(DashMap::new());
+        let waiters_b: Arc<DashMap<u64, PunchWaiter>> = Arc::new(DashMap::new());
+
+        let dispatch_a = tokio::spawn(hole_punch_dispatch_loop(sock_a.clone(), waiters_a.clone()));
+        let dispatch_b = tokio::spawn(hole_punch_dispatch_loop(sock_b.clone(), waiters_b.clone()));
+
+        let puncher_a = UdpHolePuncher::new(sock_a.clone(), waiters_a);
+        let puncher_b = UdpHolePuncher::new(sock_b.clone(), waiters_b);
+
+        let (res_a, res_b) = tokio::join!(
+            puncher_a.punch(vec![addr_b]),
+            puncher_b.punch(vec![addr_a]),
+        );
+
+        dispatch_a.abort();
+        dispatch_b.abort();
+
+        assert_eq!(res_a.unwrap(), addr_b);
+        assert_eq!(res_b.unwrap(), addr_a);
+    }
+
     #[tokio::test]
     #[should_panic]
     async fn udp_bench_with_bind_fail() {
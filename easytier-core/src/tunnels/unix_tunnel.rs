@@ -0,0 +1,300 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use easytier_rpc::TunnelInfo;
+use futures::{SinkExt, StreamExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::tunnels::{TunnelConnCounter, TunnelConnector};
+
+use super::{codec::LengthDelimitedCodec, common::FramedTunnel, Tunnel, TunnelListener};
+
+/// `unix://` has no MTU, so frames are length-delimited rather than
+/// datagram-sized like the UDP transports.
+const UNIX_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// Either a filesystem path (`unix:///tmp/easytier.sock`) or, on Linux, an
+/// abstract-namespace name with no filesystem entry (`unix://@easytier`).
+enum UnixAddr {
+    Path(PathBuf),
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Abstract(String),
+}
+
+fn parse_unix_url(addr: &url::Url) -> Result<UnixAddr, super::TunnelError> {
+    if let Some(name) = addr.host_str().filter(|h| !h.is_empty()) {
+        return Ok(UnixAddr::Abstract(name.to_owned()));
+    }
+    Ok(UnixAddr::Path(PathBuf::from(addr.path())))
+}
+
+fn unix_addr_url(unix_addr: &UnixAddr) -> url::Url {
+    let s = match unix_addr {
+        UnixAddr::Path(path) => format!("unix://{}", path.display()),
+        UnixAddr::Abstract(name) => format!("unix://@{}", name),
+    };
+    url::Url::parse(&s).unwrap()
+}
+
+#[cfg(target_os = "linux")]
+fn bind_abstract(name: &str) -> Result<std::os::unix::net::UnixListener, super::TunnelError> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)
+        .map_err(|e| super::TunnelError::CommonError(format!("unix abstract addr: {}", e)))?;
+    std::os::unix::net::UnixListener::bind_addr(&addr)
+        .map_err(|e| super::TunnelError::CommonError(format!("unix abstract bind: {}", e)))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_abstract(_name: &str) -> Result<std::os::unix::net::UnixListener, super::TunnelError> {
+    Err(super::TunnelError::CommonError(
+        "unix abstract-namespace sockets are only supported on linux".to_owned(),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn connect_abstract(name: &str) -> Result<std::os::unix::net::UnixStream, super::TunnelError> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)
+        .map_err(|e| super::TunnelError::ConnectError(format!("unix abstract addr: {}", e)))?;
+    std::os::unix::net::UnixStream::connect_addr(&addr)
+        .map_err(|e| super::TunnelError::ConnectError(format!("unix abstract connect: {}", e)))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn connect_abstract(_name: &str) -> Result<std::os::unix::net::UnixStream, super::TunnelError> {
+    Err(super::TunnelError::ConnectError(
+        "unix abstract-namespace sockets are only supported on linux".to_owned(),
+    ))
+}
+
+fn tunnel_from_stream(
+    stream: UnixStream,
+    local_url: url::Url,
+    remote_url: url::Url,
+    on_close: Option<(Arc<DashMap<u64, ()>>, u64)>,
+) -> Box<dyn Tunnel> {
+    let (recv, send) = stream.into_split();
+    let codec_stream = FramedRead::new(recv, LengthDelimitedCodec::new(UNIX_MAX_FRAME_LEN)).map(
+        |v| {
+            v.map(|b| b.freeze())
+                .map_err(|e| super::TunnelError::CommonError(format!("unix stream error: {}", e)))
+        },
+    );
+    // the underlying stream has no disconnect callback to hook into, so the
+    // conn counter is decremented once the read side naturally hits EOF.
+    let codec_stream = codec_stream.chain(futures::stream::poll_fn(move |_| {
+        if let Some((conn_count, peer_id)) = &on_close {
+            conn_count.remove(peer_id);
+        }
+        std::task::Poll::Ready(None)
+    }));
+    let codec_sink = FramedWrite::new(send, LengthDelimitedCodec::new(UNIX_MAX_FRAME_LEN))
+        .sink_map_err(|e| super::TunnelError::CommonError(format!("unix sink error: {}", e)));
+
+    FramedTunnel::new_tunnel_with_info(
+        Box::pin(codec_stream),
+        Box::pin(codec_sink),
+        TunnelInfo {
+            tunnel_type: "unix".to_owned(),
+            local_addr: local_url.into(),
+            remote_addr: remote_url.into(),
+        },
+    )
+}
+
+pub struct UnixTunnelListener {
+    addr: url::Url,
+    listener: Option<UnixListener>,
+    bind_path: Option<PathBuf>,
+    file_mode: Option<u32>,
+    next_peer_id: AtomicU64,
+    conn_count: Arc<DashMap<u64, ()>>,
+}
+
+impl UnixTunnelListener {
+    pub fn new(addr: url::Url) -> Self {
+        Self {
+            addr,
+            listener: None,
+            bind_path: None,
+            file_mode: None,
+            next_peer_id: AtomicU64::new(0),
+            conn_count: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Unix file permission bits (e.g. `0o600`) to apply to the socket file
+    /// after bind. No-op for abstract-namespace sockets, which have none.
+    pub fn set_file_mode(&mut self, mode: u32) {
+        self.file_mode = Some(mode);
+    }
+}
+
+impl Drop for UnixTunnelListener {
+    fn drop(&mut self) {
+        if let Some(path) = self.bind_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[async_trait]
+impl TunnelListener for UnixTunnelListener {
+    async fn listen(&mut self) -> Result<(), super::TunnelError> {
+        let unix_addr = parse_unix_url(&self.addr)?;
+
+        let std_listener = match &unix_addr {
+            UnixAddr::Path(path) => {
+                // a stale socket file left over from a previous, crashed run
+                // would otherwise make bind() fail with "address in use".
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                let listener = std::os::unix::net::UnixListener::bind(path)?;
+                if let Some(mode) = self.file_mode {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+                }
+                self.bind_path = Some(path.clone());
+                listener
+            }
+            UnixAddr::Abstract(name) => bind_abstract(name)?,
+        };
+        std_listener.set_nonblocking(true)?;
+
+        self.addr = unix_addr_url(&unix_addr);
+        self.listener = Some(UnixListener::from_std(std_listener)?);
+        Ok(())
+    }
+
+    async fn accept(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let listener = self.listener.as_ref().ok_or_else(|| {
+            super::TunnelError::CommonError("unix listener not listening".to_owned())
+        })?;
+
+        let (stream, _peer_addr) = listener.accept().await?;
+        let peer_id = self.next_peer_id.fetch_add(1, Ordering::Relaxed);
+        self.conn_count.insert(peer_id, ());
+
+        let local_url = self.addr.clone();
+        let remote_url = url::Url::parse(&format!("unix://peer-{}", peer_id)).unwrap();
+        Ok(tunnel_from_stream(
+            stream,
+            local_url,
+            remote_url,
+            Some((self.conn_count.clone(), peer_id)),
+        ))
+    }
+
+    fn local_url(&self) -> url::Url {
+        self.addr.clone()
+    }
+
+    fn get_conn_counter(&self) -> Arc<Box<dyn TunnelConnCounter>> {
+        struct UnixTunnelConnCounter {
+            conn_count: Arc<DashMap<u64, ()>>,
+        }
+
+        impl std::fmt::Debug for UnixTunnelConnCounter {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("UnixTunnelConnCounter")
+                    .field("conn_count", &self.conn_count.len())
+                    .finish()
+            }
+        }
+
+        impl TunnelConnCounter for UnixTunnelConnCounter {
+            fn get(&self) -> u32 {
+                self.conn_count.len() as u32
+            }
+        }
+
+        Arc::new(Box::new(UnixTunnelConnCounter {
+            conn_count: self.conn_count.clone(),
+        }))
+    }
+}
+
+pub struct UnixTunnelConnector {
+    addr: url::Url,
+}
+
+impl UnixTunnelConnector {
+    pub fn new(addr: url::Url) -> Self {
+        Self { addr }
+    }
+}
+
+#[async_trait]
+impl TunnelConnector for UnixTunnelConnector {
+    async fn connect(&mut self) -> Result<Box<dyn Tunnel>, super::TunnelError> {
+        let unix_addr = parse_unix_url(&self.addr)?;
+
+        let stream = match &unix_addr {
+            UnixAddr::Path(path) => UnixStream::connect(path).await?,
+            UnixAddr::Abstract(name) => {
+                let std_stream = connect_abstract(name)?;
+                std_stream.set_nonblocking(true)?;
+                UnixStream::from_std(std_stream)?
+            }
+        };
+
+        Ok(tunnel_from_stream(
+            stream,
+            unix_addr_url(&unix_addr),
+            self.addr.clone(),
+            None,
+        ))
+    }
+
+    fn remote_url(&self) -> url::Url {
+        self.addr.clone()
+    }
+
+    fn set_bind_addrs(&mut self, _addrs: Vec<std::net::SocketAddr>) {
+        // unix sockets have no notion of a source network interface to bind to.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tunnels::common::tests::{_tunnel_bench, _tunnel_pingpong};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn unix_pingpong() {
+        let path = std::env::temp_dir().join("easytier_unix_pingpong.sock");
+        let url = url::Url::parse(&format!("unix://{}", path.display())).unwrap();
+        let listener = UnixTunnelListener::new(url.clone());
+        let connector = UnixTunnelConnector::new(url);
+        _tunnel_pingpong(listener, connector).await
+    }
+
+    #[tokio::test]
+    async fn unix_bench() {
+        let path = std::env::temp_dir().join("easytier_unix_bench.sock");
+        let url = url::Url::parse(&format!("unix://{}", path.display())).unwrap();
+        let listener = UnixTunnelListener::new(url.clone());
+        let connector = UnixTunnelConnector::new(url);
+        _tunnel_bench(listener, connector).await
+    }
+
+    #[tokio::test]
+    #[cfg(target_os = "linux")]
+    async fn unix_abstract_pingpong() {
+        let url = url::Url::parse("unix://@easytier_unix_abstract_pingpong").unwrap();
+        let listener = UnixTunnelListener::new(url.clone());
+        let connector = UnixTunnelConnector::new(url);
+        _tunnel_pingpong(listener, connector).await
+    }
+}